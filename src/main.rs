@@ -1,36 +1,183 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use colored::*;
+use futures::StreamExt;
+use indicatif::{ProgressBar, ProgressStyle};
 use mime::Mime;
-use reqwest::{header, Client, Response, Url};
+use reqwest::{
+    cookie::{CookieStore, Jar},
+    header, multipart, Client, Response, Url,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use syntect::{parsing::SyntaxSet, highlighting::ThemeSet, easy::HighlightLines, util::{LinesWithEndings, as_24_bit_terminal_escaped}};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[derive(Parser, Debug)]
 #[clap(version = "1.0", author = "Wei L. <sunnywhy@gmail.com>")]
 struct Opts {
-    #[clap(subcommand)]
-    subcmd: Subcommand,
+    /// HTTP method: get, post, put, delete, patch, head or options
+    method: RequestMethod,
+    #[clap(parse(try_from_str = parse_url))]
+    url: String,
+    /// Custom request header "Name: Value", may be repeated
+    #[clap(short = 'H', long = "header")]
+    headers: Vec<String>,
+    /// Serialize body items as application/x-www-form-urlencoded instead of JSON
+    #[clap(long)]
+    form: bool,
+    /// Send a multipart/form-data body (implied by any `key@file` item)
+    #[clap(long)]
+    multipart: bool,
+    /// Stream the response body to disk instead of printing it
+    #[clap(short = 'd', long = "download")]
+    download: bool,
+    /// Output file path for a download; derived from the URL or Content-Disposition when omitted
+    #[clap(short = 'o', long = "output")]
+    output: Option<String>,
+    /// Follow HTTP redirects
+    #[clap(short = 'n', long = "follow")]
+    follow: bool,
+    /// Persist headers and cookies across invocations under this session name
+    #[clap(long)]
+    session: Option<String>,
+    /// Credentials for --auth-type, "user:pass", "user:" or just "user"
+    #[clap(short = 'a', long = "auth")]
+    auth: Option<Credentials>,
+    /// Authentication scheme to use with --auth: basic or bearer
+    #[clap(long = "auth-type", default_value = "basic")]
+    auth_type: AuthType,
+    /// Route the request through this proxy URL
+    #[clap(long)]
+    proxy: Option<String>,
+    /// Bypass syntax highlighting, useful when piping the output
+    #[clap(short = 'r', long = "raw")]
+    raw: bool,
+    /// Print only the response headers (no `-h` short flag: it's reserved for --help)
+    #[clap(long = "headers")]
+    headers_only: bool,
+    /// Print only the response body
+    #[clap(short = 'b', long = "body")]
+    body_only: bool,
+    /// Output styling: all, colors or none
+    #[clap(long, default_value = "all")]
+    pretty: Pretty,
+    /// Request items: key=value (JSON string), key:=value (raw JSON), key==value (query param), Header:value, key@file (upload), or a bare "-" to read the body from stdin
+    items: Vec<RequestItem>,
 }
 
-#[derive(Parser, Debug)]
-enum Subcommand {
-    Get(Get),
-    Post(Post),
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pretty {
+    All,
+    Colors,
+    None,
 }
 
-#[derive(Parser, Debug)]
-struct Get {
-    #[clap(parse(try_from_str = parse_url))]
-    url: String,
+impl FromStr for Pretty {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "all" => Ok(Self::All),
+            "colors" => Ok(Self::Colors),
+            "none" => Ok(Self::None),
+            _ => Err(anyhow!("Unsupported --pretty value: {}", s)),
+        }
+    }
 }
 
-#[derive(Parser, Debug)]
-struct Post {
-    #[clap(parse(try_from_str = parse_url))]
-    url: String,
-    body: Vec<KvPair>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+    Patch,
+    Head,
+    Options,
+}
+
+impl FromStr for RequestMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "get" => Ok(Self::Get),
+            "post" => Ok(Self::Post),
+            "put" => Ok(Self::Put),
+            "delete" => Ok(Self::Delete),
+            "patch" => Ok(Self::Patch),
+            "head" => Ok(Self::Head),
+            "options" => Ok(Self::Options),
+            _ => Err(anyhow!("Unsupported HTTP method: {}", s)),
+        }
+    }
+}
+
+impl From<RequestMethod> for reqwest::Method {
+    fn from(method: RequestMethod) -> Self {
+        match method {
+            RequestMethod::Get => reqwest::Method::GET,
+            RequestMethod::Post => reqwest::Method::POST,
+            RequestMethod::Put => reqwest::Method::PUT,
+            RequestMethod::Delete => reqwest::Method::DELETE,
+            RequestMethod::Patch => reqwest::Method::PATCH,
+            RequestMethod::Head => reqwest::Method::HEAD,
+            RequestMethod::Options => reqwest::Method::OPTIONS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Credentials {
+    user: String,
+    password: Option<String>,
+}
+
+impl FromStr for Credentials {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.split_once(':') {
+            Some((user, password)) if !password.is_empty() => Self {
+                user: user.to_string(),
+                password: Some(password.to_string()),
+            },
+            Some((user, _)) => Self {
+                user: user.to_string(),
+                password: None,
+            },
+            None => Self {
+                user: s.to_string(),
+                password: None,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuthType {
+    Basic,
+    Bearer,
+}
+
+impl FromStr for AuthType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "basic" => Ok(Self::Basic),
+            "bearer" => Ok(Self::Bearer),
+            _ => Err(anyhow!("Unsupported --auth-type value: {}", s)),
+        }
+    }
 }
 
 fn parse_url(url: &str) -> Result<String> {
@@ -39,23 +186,267 @@ fn parse_url(url: &str) -> Result<String> {
     Ok(url.into())
 }
 
+/// Parse repeatable "Name: Value" strings into a `HeaderMap`.
+fn parse_headers(raw: &[String]) -> Result<header::HeaderMap> {
+    let mut map = header::HeaderMap::new();
+    for item in raw {
+        let (name, value) = item
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Failed to parse header '{}', expected Name: Value", item))?;
+        let name = header::HeaderName::from_bytes(name.trim().as_bytes())?;
+        let value = header::HeaderValue::from_str(value.trim())?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
 #[derive(Debug, PartialEq, Eq)]
-struct KvPair {
-    k: String,
-    v: String,
+enum RequestItem {
+    /// key=value, a string field injected into the JSON body
+    JsonField(String, String),
+    /// key:=value, a raw JSON value injected verbatim into the body
+    JsonRaw(String, String),
+    /// key==value, a URL query parameter
+    Query(String, String),
+    /// Name:value, a request header
+    Header(String, String),
+    /// key@path, a file to attach to a multipart body
+    File(String, String),
+    /// "-", read the raw request body from stdin
+    Raw(String),
+}
+
+/// Separator kinds recognised in a request item, longest match wins.
+enum Separator {
+    JsonRaw,
+    Query,
+    JsonField,
+    Header,
+    File,
+}
+
+/// Scan `s` for the first unescaped `:=`, `==`, `=`, `:` or `@`, returning
+/// its byte offset, width and kind.
+fn find_separator(s: &str) -> Option<(usize, usize, Separator)> {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b':' if bytes.get(i + 1) == Some(&b'=') => return Some((i, 2, Separator::JsonRaw)),
+            b'=' if bytes.get(i + 1) == Some(&b'=') => return Some((i, 2, Separator::Query)),
+            b'=' => return Some((i, 1, Separator::JsonField)),
+            b':' => return Some((i, 1, Separator::Header)),
+            b'@' => return Some((i, 1, Separator::File)),
+            _ => i += 1,
+        }
+    }
+    None
 }
 
-impl FromStr for KvPair {
+impl FromStr for RequestItem {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split('=');
-        let err = || anyhow!(format!("Failed to parse {}", s));
-        Ok(Self {
-            k: (split.next().ok_or_else(err)?).to_string(),
-            v: (split.next().ok_or_else(err)?).to_string(),
+        if s == "-" {
+            return Ok(Self::Raw(s.to_string()));
+        }
+        let (pos, width, kind) = find_separator(s)
+            .ok_or_else(|| anyhow!("Failed to parse request item '{}'", s))?;
+        let key = s[..pos].replace('\\', "").to_string();
+        let value = s[pos + width..].to_string();
+        Ok(match kind {
+            Separator::JsonRaw => Self::JsonRaw(key, value),
+            Separator::Query => Self::Query(key, value),
+            Separator::JsonField => Self::JsonField(key, value),
+            Separator::Header => Self::Header(key, value),
+            Separator::File => Self::File(key, value),
+        })
+    }
+}
+
+/// Build the JSON request body from `key=value`/`key:=value` items.
+fn build_body(items: &[RequestItem]) -> Result<Value> {
+    let mut map = Map::new();
+    for item in items {
+        match item {
+            RequestItem::JsonField(k, v) => {
+                map.insert(k.clone(), Value::String(v.clone()));
+            }
+            RequestItem::JsonRaw(k, v) => {
+                let value = serde_json::from_str(v)
+                    .map_err(|_| anyhow!("Invalid JSON value for '{}': {}", k, v))?;
+                map.insert(k.clone(), value);
+            }
+            _ => {}
+        }
+    }
+    Ok(Value::Object(map))
+}
+
+/// Collect `key==value` items into query parameters.
+fn build_query(items: &[RequestItem]) -> Vec<(String, String)> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            RequestItem::Query(k, v) => Some((k.clone(), v.clone())),
+            _ => None,
         })
+        .collect()
+}
+
+/// Collect `Header:value` items into a `HeaderMap`.
+fn build_item_headers(items: &[RequestItem]) -> Result<header::HeaderMap> {
+    let mut map = header::HeaderMap::new();
+    for item in items {
+        if let RequestItem::Header(k, v) = item {
+            let name = header::HeaderName::from_bytes(k.trim().as_bytes())?;
+            let value = header::HeaderValue::from_str(v.trim())?;
+            map.insert(name, value);
+        }
     }
+    Ok(map)
+}
+
+/// Whether any item attaches a named file, which implies a multipart body.
+/// A `File` item with an empty key (a bare "@path") is a raw body instead.
+fn has_file_items(items: &[RequestItem]) -> bool {
+    items
+        .iter()
+        .any(|item| matches!(item, RequestItem::File(key, _) if !key.is_empty()))
+}
+
+/// Collect `key=value` items into `application/x-www-form-urlencoded` pairs.
+fn build_form(items: &[RequestItem]) -> Vec<(String, String)> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            RequestItem::JsonField(k, v) => Some((k.clone(), v.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build a multipart form from `key=value` text fields and `key@path` files.
+async fn build_multipart(items: &[RequestItem]) -> Result<multipart::Form> {
+    let mut form = multipart::Form::new();
+    for item in items {
+        match item {
+            RequestItem::JsonField(k, v) => {
+                form = form.text(k.clone(), v.clone());
+            }
+            RequestItem::File(k, path) => {
+                let file_name = Path::new(path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.clone());
+                let mime = mime_guess::from_path(path).first_or_octet_stream();
+                let bytes = tokio::fs::read(path)
+                    .await
+                    .map_err(|e| anyhow!("Failed to read file '{}': {}", path, e))?;
+                let part = multipart::Part::bytes(bytes)
+                    .file_name(file_name)
+                    .mime_str(mime.as_ref())?;
+                form = form.part(k.clone(), part);
+            }
+            _ => {}
+        }
+    }
+    Ok(form)
+}
+
+/// Where to read a raw (non-form, non-JSON) request body from.
+enum RawBodySource {
+    Stdin,
+    File(String),
+}
+
+/// Find the item that asks for a raw body: a bare "-" (stdin) or a bare
+/// "@path" (no key), which reads the body straight from a file.
+fn raw_body_source(items: &[RequestItem]) -> Option<RawBodySource> {
+    items.iter().find_map(|item| match item {
+        RequestItem::Raw(marker) if marker == "-" => Some(RawBodySource::Stdin),
+        RequestItem::File(key, path) if key.is_empty() => Some(RawBodySource::File(path.clone())),
+        _ => None,
+    })
+}
+
+/// Read the raw body bytes and guess a `Content-Type` for them.
+async fn read_raw_body(source: RawBodySource) -> Result<(Vec<u8>, Mime)> {
+    match source {
+        RawBodySource::Stdin => {
+            let mut buf = Vec::new();
+            tokio::io::stdin().read_to_end(&mut buf).await?;
+            let mime = if serde_json::from_slice::<Value>(&buf).is_ok() {
+                mime::APPLICATION_JSON
+            } else {
+                mime::TEXT_PLAIN
+            };
+            Ok((buf, mime))
+        }
+        RawBodySource::File(path) => {
+            let bytes = tokio::fs::read(&path)
+                .await
+                .map_err(|e| anyhow!("Failed to read file '{}': {}", path, e))?;
+            let mime = mime_guess::from_path(&path).first_or_octet_stream();
+            Ok((bytes, mime))
+        }
+    }
+}
+
+/// Headers and cookies persisted across invocations of a `--session`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Session {
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    cookies: HashMap<String, String>,
+}
+
+fn session_path(name: &str) -> Result<PathBuf> {
+    let mut dir = dirs::config_dir().ok_or_else(|| anyhow!("Could not determine config directory"))?;
+    dir.push("pie");
+    dir.push("sessions");
+    std::fs::create_dir_all(&dir)?;
+    dir.push(format!("{}.json", name));
+    Ok(dir)
+}
+
+fn load_session(name: &str) -> Result<Session> {
+    let path = session_path(name)?;
+    if !path.exists() {
+        return Ok(Session::default());
+    }
+    let data = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data).unwrap_or_default())
+}
+
+fn save_session(name: &str, session: &Session) -> Result<()> {
+    let path = session_path(name)?;
+    std::fs::write(path, serde_json::to_string_pretty(session)?)?;
+    Ok(())
+}
+
+fn url_origin(url: &Url) -> String {
+    format!("{}://{}", url.scheme(), url.host_str().unwrap_or_default())
+}
+
+/// Merge this run's custom headers and any cookies the jar picked up back into the session file.
+fn persist_session(name: &str, opts: &Opts, response: &Response, jar: &Jar) -> Result<()> {
+    let mut session = load_session(name).unwrap_or_default();
+
+    for raw in &opts.headers {
+        if let Some((k, v)) = raw.split_once(':') {
+            session.headers.insert(k.trim().to_string(), v.trim().to_string());
+        }
+    }
+
+    if let Some(cookie_header) = jar.cookies(response.url()) {
+        let origin = url_origin(response.url());
+        session.cookies.insert(origin, cookie_header.to_str()?.to_string());
+    }
+
+    save_session(name, &session)
 }
 
 #[tokio::main]
@@ -65,45 +456,178 @@ async fn main() -> Result<()> {
     let mut headers = header::HeaderMap::new();
     headers.insert("X-POWERED-BY", "Rust".parse()?);
     headers.insert(header::USER_AGENT, "Rust Httpie".parse()?);
+    headers.extend(parse_headers(&opts.headers)?);
 
-    let client = Client::builder().default_headers(headers).build()?;
-    let result = match opts.subcmd {
-        Subcommand::Get(ref args) => get(client, args).await?,
-        Subcommand::Post(ref args) => post(client, args).await?,
+    let redirect_policy = if opts.follow {
+        reqwest::redirect::Policy::limited(10)
+    } else {
+        reqwest::redirect::Policy::none()
     };
 
-    Ok(result)
+    let jar = Arc::new(Jar::default());
+    if let Some(name) = &opts.session {
+        let session = load_session(name)?;
+        for (k, v) in &session.headers {
+            if let (Ok(name), Ok(value)) = (
+                header::HeaderName::from_bytes(k.as_bytes()),
+                header::HeaderValue::from_str(v),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+        if let Ok(url) = Url::parse(&opts.url) {
+            for (origin, cookie_header) in &session.cookies {
+                if *origin == url_origin(&url) {
+                    // `cookie_header` is a `Cookie` request header, "name=value; name2=value2";
+                    // `add_cookie_str` only parses a single pair per call, so replay each one.
+                    for pair in cookie_header.split("; ") {
+                        jar.add_cookie_str(pair, &url);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut client_builder = Client::builder()
+        .default_headers(headers)
+        .redirect(redirect_policy)
+        .cookie_provider(jar.clone());
+
+    if let Some(proxy) = &opts.proxy {
+        client_builder = client_builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+
+    let client = client_builder.build()?;
+    run(client, &opts, jar).await
 }
 
-async fn post(client: Client, args: &Post) -> Result<()> {
-    let mut body = HashMap::new();
-    for kv_pair in args.body.iter() {
-        body.insert(&kv_pair.k, &kv_pair.v);
+async fn run(client: Client, opts: &Opts, jar: Arc<Jar>) -> Result<()> {
+    let query = build_query(&opts.items);
+    let item_headers = build_item_headers(&opts.items)?;
+
+    let mut request = client
+        .request(opts.method.into(), &opts.url)
+        .query(&query)
+        .headers(item_headers);
+
+    if let Some(creds) = &opts.auth {
+        request = match opts.auth_type {
+            AuthType::Basic => request.basic_auth(&creds.user, creds.password.clone()),
+            AuthType::Bearer => {
+                let token = creds.password.clone().unwrap_or_else(|| creds.user.clone());
+                request.bearer_auth(token)
+            }
+        };
     }
 
-    let response = client.post(&args.url).json(&body).send().await?;
+    if let Some(source) = raw_body_source(&opts.items) {
+        let (bytes, mime) = read_raw_body(source).await?;
+        request = request.header(header::CONTENT_TYPE, mime.as_ref()).body(bytes);
+    } else if opts.multipart || has_file_items(&opts.items) {
+        request = request.multipart(build_multipart(&opts.items).await?);
+    } else if opts.form {
+        let form = build_form(&opts.items);
+        if !form.is_empty() {
+            request = request.form(&form);
+        }
+    } else {
+        let body = build_body(&opts.items)?;
+        if matches!(&body, Value::Object(map) if !map.is_empty()) {
+            request = request.json(&body);
+        }
+    }
 
-    Ok(print_response(response).await?)
+    let response = request.send().await?;
+    if let Some(name) = &opts.session {
+        persist_session(name, opts, &response, &jar)?;
+    }
+
+    if opts.download {
+        download_response(response, opts).await
+    } else {
+        print_response(response, opts).await
+    }
 }
 
-async fn get(client: Client, args: &Get) -> Result<()> {
-    let response = client.get(&args.url).send().await?;
-    Ok(print_response(response).await?)
+/// Stream `response` to disk, rendering a progress bar from `Content-Length`.
+async fn download_response(response: Response, opts: &Opts) -> Result<()> {
+    let path = opts
+        .output
+        .clone()
+        .unwrap_or_else(|| derive_filename(&response, &opts.url));
+
+    let total = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+
+    let pb = match total {
+        Some(len) => ProgressBar::new(len),
+        None => ProgressBar::new_spinner(),
+    };
+    if let Ok(style) = ProgressStyle::with_template("{bar:40.cyan/blue} {bytes}/{total_bytes} ({eta})") {
+        pb.set_style(style);
+    }
+
+    let mut file = tokio::fs::File::create(&path).await?;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk).await?;
+        pb.inc(chunk.len() as u64);
+    }
+    pb.finish_with_message(format!("Saved to {}", path));
+    Ok(())
 }
 
-async fn print_response(response: Response) -> Result<()> {
-    print_status(&response);
-    print_headers(&response);
-    let mime = get_content_type(&response);
-    let body = response.text().await?;
-    print_body(mime, &body);
+/// Pick a filename from `Content-Disposition`, falling back to the URL's last segment.
+fn derive_filename(response: &Response, url: &str) -> String {
+    response
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_content_disposition_filename)
+        .unwrap_or_else(|| {
+            Url::parse(url)
+                .ok()
+                .and_then(|u| {
+                    u.path_segments()
+                        .and_then(|mut segments| segments.next_back().map(str::to_string))
+                })
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "download".to_string())
+        })
+}
+
+fn parse_content_disposition_filename(value: &str) -> Option<String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .find_map(|part| part.strip_prefix("filename="))
+        .map(|name| name.trim_matches('"').to_string())
+}
+
+async fn print_response(response: Response, opts: &Opts) -> Result<()> {
+    if !opts.body_only {
+        print_status(&response, opts);
+        print_headers(&response, opts);
+    }
+
+    if !opts.headers_only {
+        let mime = get_content_type(&response);
+        let body = response.text().await?;
+        print_body(mime, &body, opts);
+    }
+
     Ok(())
 }
 
-fn print_body(mime: Option<Mime>, body: &str) {
+fn print_body(mime: Option<Mime>, body: &str, opts: &Opts) {
+    let highlight = !opts.raw && opts.pretty != Pretty::None;
     match mime {
-        Some(v) if v == mime::APPLICATION_JSON => print_syntect(body, "json"),
-        Some(v) if v == mime::TEXT_HTML => print_syntect(body, "html"),
+        Some(v) if highlight && v == mime::APPLICATION_JSON => print_syntect(body, "json"),
+        Some(v) if highlight && v == mime::TEXT_HTML => print_syntect(body, "html"),
         _ => println!("{}", body),
     }
 }
@@ -128,16 +652,24 @@ fn get_content_type(response: &Response) -> Option<Mime> {
         .map(|v| v.to_str().unwrap().parse().unwrap())
 }
 
-fn print_headers(response: &Response) {
+fn print_headers(response: &Response, opts: &Opts) {
     for (name, value) in response.headers() {
-        println!("{}: {:?}", name.to_string().green(), value);
+        if opts.pretty == Pretty::None {
+            println!("{}: {:?}", name, value);
+        } else {
+            println!("{}: {:?}", name.to_string().green(), value);
+        }
     }
     println!();
 }
 
-fn print_status(response: &Response) {
-    let status = format!("{:?} {}", response.version(), response.status()).blue();
-    println!("{}\n", status);
+fn print_status(response: &Response, opts: &Opts) {
+    let status = format!("{:?} {}", response.version(), response.status());
+    if opts.pretty == Pretty::None {
+        println!("{}\n", status);
+    } else {
+        println!("{}\n", status.blue());
+    }
 }
 
 #[cfg(test)]
@@ -152,21 +684,121 @@ mod tests {
     }
 
     #[test]
-    fn parse_kv_pair_works() {
-        assert!(KvPair::from_str("a").is_err());
+    fn parse_request_method_works() {
+        assert_eq!(RequestMethod::from_str("GET").unwrap(), RequestMethod::Get);
+        assert_eq!(RequestMethod::from_str("put").unwrap(), RequestMethod::Put);
+        assert!(RequestMethod::from_str("fetch").is_err());
+    }
+
+    #[test]
+    fn parse_headers_works() {
+        let map = parse_headers(&["X-Api-Key: abc123".to_string()]).unwrap();
+        assert_eq!(map.get("x-api-key").unwrap(), "abc123");
+        assert!(parse_headers(&["no-colon".to_string()]).is_err());
+    }
+
+    #[test]
+    fn parse_request_item_works() {
+        assert!(RequestItem::from_str("a").is_err());
+        assert_eq!(
+            RequestItem::from_str("name=bob").unwrap(),
+            RequestItem::JsonField("name".into(), "bob".into())
+        );
+        assert_eq!(
+            RequestItem::from_str("age:=30").unwrap(),
+            RequestItem::JsonRaw("age".into(), "30".into())
+        );
+        assert_eq!(
+            RequestItem::from_str("q==search").unwrap(),
+            RequestItem::Query("q".into(), "search".into())
+        );
+        assert_eq!(
+            RequestItem::from_str("X-Api-Key:abc").unwrap(),
+            RequestItem::Header("X-Api-Key".into(), "abc".into())
+        );
+        assert_eq!(
+            RequestItem::from_str("avatar@/tmp/photo.png").unwrap(),
+            RequestItem::File("avatar".into(), "/tmp/photo.png".into())
+        );
+        assert_eq!(
+            RequestItem::from_str("-").unwrap(),
+            RequestItem::Raw("-".into())
+        );
+        assert_eq!(
+            raw_body_source(&[RequestItem::Raw("-".into())]).is_some(),
+            true
+        );
+    }
+
+    #[test]
+    fn parse_pretty_works() {
+        assert_eq!(Pretty::from_str("all").unwrap(), Pretty::All);
+        assert_eq!(Pretty::from_str("NONE").unwrap(), Pretty::None);
+        assert!(Pretty::from_str("rainbow").is_err());
+    }
+
+    #[test]
+    fn parse_credentials_works() {
         assert_eq!(
-            KvPair::from_str("a=1").unwrap(),
-            KvPair {
-                k: "a".into(),
-                v: "1".into(),
+            Credentials::from_str("alice:secret").unwrap(),
+            Credentials {
+                user: "alice".into(),
+                password: Some("secret".into()),
             }
         );
         assert_eq!(
-            KvPair::from_str("b=").unwrap(),
-            KvPair {
-                k: "b".into(),
-                v: "".into(),
+            Credentials::from_str("alice:").unwrap(),
+            Credentials {
+                user: "alice".into(),
+                password: None,
             }
         );
+        assert_eq!(
+            Credentials::from_str("alice").unwrap(),
+            Credentials {
+                user: "alice".into(),
+                password: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_auth_type_works() {
+        assert_eq!(AuthType::from_str("basic").unwrap(), AuthType::Basic);
+        assert_eq!(AuthType::from_str("Bearer").unwrap(), AuthType::Bearer);
+        assert!(AuthType::from_str("digest").is_err());
+    }
+
+    #[test]
+    fn build_form_works() {
+        let items = vec![
+            RequestItem::from_str("name=bob").unwrap(),
+            RequestItem::from_str("q==search").unwrap(),
+        ];
+        assert_eq!(build_form(&items), vec![("name".to_string(), "bob".to_string())]);
+    }
+
+    #[test]
+    fn parse_content_disposition_filename_works() {
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename=\"report.pdf\""),
+            Some("report.pdf".to_string())
+        );
+        assert_eq!(parse_content_disposition_filename("inline"), None);
+    }
+
+    #[test]
+    fn build_body_works() {
+        let items = vec![
+            RequestItem::from_str("name=bob").unwrap(),
+            RequestItem::from_str("age:=30").unwrap(),
+            RequestItem::from_str("active:=true").unwrap(),
+            RequestItem::from_str("q==search").unwrap(),
+        ];
+        let body = build_body(&items).unwrap();
+        assert_eq!(body["name"], Value::String("bob".into()));
+        assert_eq!(body["age"], 30);
+        assert_eq!(body["active"], true);
+        assert!(body.get("q").is_none());
     }
 }